@@ -0,0 +1,254 @@
+//! Derive macro for [rat_focus::HasFocus], companion crate to `rat-focus`.
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Field, Fields, Ident};
+
+/// Builds the `HasFocus` implementation for a widget-state struct by
+/// collecting the annotated fields' `FocusFlag`s, in declaration order,
+/// into a `Focus`, and by pointing `container()`/`area()` directly at
+/// the struct's own `ContainerFlag`/`Rect` fields (not the defaulted
+/// trait methods, which would otherwise recurse back through `focus()`).
+///
+/// Field attributes:
+/// - `#[focus]` - a plain widget field, added via `Focus::add`.
+/// - `#[focus(container)]` - a nested composite field whose own type
+///   implements `HasFocus`; spliced in via `Focus::add_container`.
+/// - `#[focus(skip)]` - same as leaving the field unannotated.
+/// - `#[focus(order = N)]` - overrides the field's position in the
+///   resulting tab order (ties keep declaration order).
+/// - `#[focus(flag)]` - required, exactly once: the `ContainerFlag` field
+///   that identifies this struct as a container in its own right.
+/// - `#[focus(area)]` - the `Rect` field backing this struct's own area.
+///   Defaults to `Rect::default()` if no field carries it.
+#[proc_macro_derive(HasFocus, attributes(focus))]
+pub fn derive_has_focus(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+
+    let Data::Struct(data) = &input.data else {
+        return syn::Error::new_spanned(&input, "HasFocus can only be derived for structs")
+            .to_compile_error()
+            .into();
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return syn::Error::new_spanned(&input, "HasFocus requires named fields")
+            .to_compile_error()
+            .into();
+    };
+
+    let mut entries = Vec::new();
+    let mut flag_field: Option<Ident> = None;
+    let mut area_field: Option<Ident> = None;
+
+    for (position, field) in fields.named.iter().enumerate() {
+        let Some(ident) = &field.ident else {
+            continue;
+        };
+        let spec = match focus_attr(field) {
+            Ok(Some(spec)) => spec,
+            Ok(None) => continue,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        match spec {
+            FieldSpec::Widget { order } => {
+                let order = order.unwrap_or(position as i64);
+                entries.push((order, position, quote! { focus.add(&self.#ident); }));
+            }
+            FieldSpec::Container { order } => {
+                let order = order.unwrap_or(position as i64);
+                entries.push((
+                    order,
+                    position,
+                    quote! { focus.add_container(&self.#ident); },
+                ));
+            }
+            FieldSpec::Flag => {
+                if flag_field.is_some() {
+                    return syn::Error::new_spanned(
+                        field,
+                        "only one field may be marked `#[focus(flag)]`",
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+                flag_field = Some(ident.clone());
+            }
+            FieldSpec::Area => {
+                area_field = Some(ident.clone());
+            }
+        }
+    }
+
+    let Some(flag_field) = flag_field else {
+        return syn::Error::new_spanned(
+            &input,
+            "HasFocus needs exactly one field marked `#[focus(flag)]` \
+             holding this struct's own ContainerFlag",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    entries.sort_by_key(|(order, position, _)| (*order, *position));
+    let adds = entries.into_iter().map(|(_, _, add)| add);
+
+    let area_impl = match area_field {
+        Some(area_field) => quote! { self.#area_field },
+        None => quote! { ::ratatui::layout::Rect::default() },
+    };
+
+    let expanded = quote! {
+        impl ::rat_focus::HasFocus for #name {
+            fn focus(&self) -> ::rat_focus::Focus {
+                let mut focus = ::rat_focus::Focus::default();
+                #(#adds)*
+                focus
+            }
+
+            fn container(&self) -> ::std::option::Option<::rat_focus::ContainerFlag> {
+                ::std::option::Option::Some(self.#flag_field.clone())
+            }
+
+            fn area(&self) -> ::ratatui::layout::Rect {
+                #area_impl
+            }
+        }
+    };
+
+    expanded.into()
+}
+
+#[derive(Debug, PartialEq)]
+enum FieldSpec {
+    Widget { order: Option<i64> },
+    Container { order: Option<i64> },
+    Flag,
+    Area,
+}
+
+/// Reads the `#[focus(..)]` attribute of a field, if any.
+/// Returns `None` for unannotated/skipped fields.
+fn focus_attr(field: &Field) -> syn::Result<Option<FieldSpec>> {
+    for attr in &field.attrs {
+        if !attr.path().is_ident("focus") {
+            continue;
+        }
+        if matches!(attr.meta, syn::Meta::Path(_)) {
+            return Ok(Some(FieldSpec::Widget { order: None }));
+        }
+
+        let mut is_container = false;
+        let mut is_flag = false;
+        let mut is_area = false;
+        let mut skip = false;
+        let mut order = None;
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("container") {
+                is_container = true;
+                Ok(())
+            } else if meta.path.is_ident("flag") {
+                is_flag = true;
+                Ok(())
+            } else if meta.path.is_ident("area") {
+                is_area = true;
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else if meta.path.is_ident("order") {
+                let value = meta.value()?;
+                let lit: syn::LitInt = value.parse()?;
+                order = Some(lit.base10_parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported `focus` attribute"))
+            }
+        })?;
+
+        if skip {
+            return Ok(None);
+        }
+        if is_flag {
+            return Ok(Some(FieldSpec::Flag));
+        }
+        if is_area {
+            return Ok(Some(FieldSpec::Area));
+        }
+        return Ok(Some(if is_container {
+            FieldSpec::Container { order }
+        } else {
+            FieldSpec::Widget { order }
+        }));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse::Parser;
+    use syn::parse_quote;
+
+    fn field(tokens: proc_macro2::TokenStream) -> Field {
+        syn::Field::parse_named
+            .parse2(tokens)
+            .expect("valid named field")
+    }
+
+    #[test]
+    fn unannotated_field_is_skipped() {
+        let f = field(parse_quote! { plain: Rect });
+        assert_eq!(focus_attr(&f).unwrap(), None);
+    }
+
+    #[test]
+    fn bare_focus_is_a_widget() {
+        let f = field(parse_quote! { #[focus] widget: FocusFlag });
+        assert_eq!(
+            focus_attr(&f).unwrap(),
+            Some(FieldSpec::Widget { order: None })
+        );
+    }
+
+    #[test]
+    fn focus_container_is_recognized() {
+        let f = field(parse_quote! { #[focus(container)] sub: SubState });
+        assert_eq!(
+            focus_attr(&f).unwrap(),
+            Some(FieldSpec::Container { order: None })
+        );
+    }
+
+    #[test]
+    fn focus_skip_is_skipped() {
+        let f = field(parse_quote! { #[focus(skip)] ignored: FocusFlag });
+        assert_eq!(focus_attr(&f).unwrap(), None);
+    }
+
+    #[test]
+    fn focus_order_overrides_position() {
+        let f = field(parse_quote! { #[focus(order = 3)] widget: FocusFlag });
+        assert_eq!(
+            focus_attr(&f).unwrap(),
+            Some(FieldSpec::Widget { order: Some(3) })
+        );
+    }
+
+    #[test]
+    fn focus_flag_is_recognized() {
+        let f = field(parse_quote! { #[focus(flag)] container: ContainerFlag });
+        assert_eq!(focus_attr(&f).unwrap(), Some(FieldSpec::Flag));
+    }
+
+    #[test]
+    fn focus_area_is_recognized() {
+        let f = field(parse_quote! { #[focus(area)] area: Rect });
+        assert_eq!(focus_attr(&f).unwrap(), Some(FieldSpec::Area));
+    }
+
+    #[test]
+    fn unsupported_attribute_is_an_error() {
+        let f = field(parse_quote! { #[focus(bogus)] widget: FocusFlag });
+        assert!(focus_attr(&f).is_err());
+    }
+}
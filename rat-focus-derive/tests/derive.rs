@@ -0,0 +1,70 @@
+//! Exercises the generated `focus()`/`container()`/`area()` impl end to
+//! end, rather than just the attribute parser in `src/lib.rs`.
+use rat_focus::{ContainerFlag, Focus, FocusFlag, HasFocus, HasFocusFlag};
+use rat_focus_derive::HasFocus;
+use ratatui::layout::Rect;
+
+struct Child {
+    focus: FocusFlag,
+    area: Rect,
+}
+
+impl HasFocusFlag for Child {
+    fn focus(&self) -> FocusFlag {
+        self.focus.clone()
+    }
+
+    fn area(&self) -> Rect {
+        self.area
+    }
+}
+
+#[derive(HasFocus)]
+struct Dialog {
+    #[focus(flag)]
+    container: ContainerFlag,
+    #[focus(area)]
+    area: Rect,
+    #[focus]
+    a: Child,
+    #[focus]
+    b: Child,
+}
+
+fn rect(x: u16, y: u16, width: u16, height: u16) -> Rect {
+    Rect {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+#[test]
+fn derived_container_and_area_are_the_struct_s_own() {
+    let dialog = Dialog {
+        container: ContainerFlag::named("dialog"),
+        area: rect(0, 0, 10, 10),
+        a: Child {
+            focus: FocusFlag::named("a"),
+            area: rect(0, 0, 10, 1),
+        },
+        b: Child {
+            focus: FocusFlag::named("b"),
+            area: rect(0, 1, 10, 1),
+        },
+    };
+
+    assert!(dialog.container() == Some(dialog.container.clone()));
+    assert_eq!(dialog.area(), rect(0, 0, 10, 10));
+
+    let focus = Focus::new_container(&dialog);
+    focus.focus(&dialog.a);
+
+    assert!(dialog.is_focused());
+    assert!(dialog.gained_focus());
+
+    assert!(focus.next());
+    assert!(dialog.is_focused());
+    assert!(!dialog.lost_focus());
+}
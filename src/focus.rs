@@ -0,0 +1,946 @@
+//! Focus handling.
+use crate::event::{crossterm, ct_event, ConsumedEvent, HandleEvent, MouseOnly, Outcome, Regular};
+use crate::{ContainerFlag, FocusFlag, FocusReason, HasFocus, HasFocusFlag, Navigation, ZRect};
+use ratatui::layout::Rect;
+use std::sync::{Arc, RwLock};
+
+/// One focusable widget as tracked by [Focus].
+#[derive(Debug, Clone)]
+struct FocusEntry {
+    focus: FocusFlag,
+    navigable: Navigation,
+    area: Rect,
+    z_areas: Vec<ZRect>,
+}
+
+/// A sub-range of [FocusCore::list] that belongs to a container.
+#[derive(Debug, Clone)]
+struct ContainerBound {
+    container: ContainerFlag,
+    area: Rect,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Default)]
+struct FocusCore {
+    /// Set if this Focus is itself the focus-list of a container widget.
+    container: Option<ContainerFlag>,
+    area: Rect,
+    /// Focusable widgets, in tab order.
+    list: Vec<FocusEntry>,
+    /// Sub-containers spliced into `list` via [Focus::add_container].
+    containers: Vec<ContainerBound>,
+    /// If set, navigation is confined to this container's sub-range of `list`.
+    /// See [Focus::lock]/[Focus::unlock].
+    lock: Option<ContainerFlag>,
+}
+
+/// Finds the `[start, end)` range of `list` that navigation is confined to,
+/// if a container is currently locked.
+fn locked_bound(core: &FocusCore) -> Option<(usize, usize)> {
+    let lock = core.lock.as_ref()?;
+    if core.container.as_ref() == Some(lock) {
+        return Some((0, core.list.len()));
+    }
+    core.containers
+        .iter()
+        .find(|b| b.container == *lock)
+        .map(|b| (b.start, b.end))
+}
+
+/// Direction for spatial (arrow-key) focus navigation.
+/// See [Focus::focus_left], [Focus::focus_right], [Focus::focus_up], [Focus::focus_down].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Handles the focus for a list of widgets.
+///
+/// Create one with [Focus::new] and feed it crossterm events via
+/// [handle_focus]/[handle_mouse_focus]. A `Focus` is cheap to clone,
+/// like [FocusFlag] it's a handle to shared state.
+///
+/// For composite/container widgets, build the sub-focus of the
+/// container with [Focus::new_container] and splice it into the
+/// parent's `Focus` with [Focus::add_container].
+#[derive(Debug, Default, Clone)]
+pub struct Focus {
+    core: Arc<RwLock<FocusCore>>,
+}
+
+fn navigable_as_destination(nav: Navigation) -> bool {
+    matches!(
+        nav,
+        Navigation::Regular
+            | Navigation::Reach
+            | Navigation::ReachLeaveFront
+            | Navigation::ReachLeaveBack
+    )
+}
+
+fn can_leave_forward(nav: Navigation) -> bool {
+    !matches!(nav, Navigation::Reach | Navigation::ReachLeaveFront)
+}
+
+fn can_leave_backward(nav: Navigation) -> bool {
+    !matches!(nav, Navigation::Reach | Navigation::ReachLeaveBack)
+}
+
+/// Right/Down read as "forward" and Left/Up as "backward", matching the
+/// same notion of direction that `ReachLeaveFront`/`ReachLeaveBack` use
+/// for Tab/BackTab.
+fn direction_leaves_forward(dir: Direction) -> bool {
+    matches!(dir, Direction::Right | Direction::Down)
+}
+
+fn center(area: Rect) -> (i32, i32) {
+    (
+        area.x as i32 + area.width as i32 / 2,
+        area.y as i32 + area.height as i32 / 2,
+    )
+}
+
+impl Focus {
+    /// New focus-list for a plain set of widgets, in the given order.
+    pub fn new(list: &[&dyn HasFocusFlag]) -> Self {
+        let mut focus = Self::default();
+        for widget in list {
+            focus.add(*widget);
+        }
+        focus
+    }
+
+    /// New focus-list for the contents of a container widget.
+    ///
+    /// The resulting `Focus` summarizes gained/lost for `container`'s
+    /// own [ContainerFlag] in addition to holding its children.
+    pub fn new_container(container: &dyn HasFocus) -> Self {
+        let focus = container.focus();
+        {
+            let mut core = focus.core.write().unwrap();
+            core.container = container.container();
+            core.area = container.area();
+        }
+        focus
+    }
+
+    /// Appends a single widget to the focus-list.
+    pub fn add(&mut self, widget: &dyn HasFocusFlag) -> &mut Self {
+        let mut core = self.core.write().unwrap();
+        core.list.push(FocusEntry {
+            focus: widget.focus(),
+            navigable: widget.navigable(),
+            area: widget.area(),
+            z_areas: widget.z_areas().to_vec(),
+        });
+        self
+    }
+
+    /// Splices the focus-list of a sub-container in, recording its
+    /// bounds so the container's own gained/lost can be summarized.
+    pub fn add_container(&mut self, container: &dyn HasFocus) -> &mut Self {
+        let sub = Focus::new_container(container);
+        let sub_core = sub.core.read().unwrap();
+
+        let mut core = self.core.write().unwrap();
+        let start = core.list.len();
+        core.list.extend(sub_core.list.iter().cloned());
+        let end = core.list.len();
+
+        if let Some(container_flag) = sub_core.container.clone() {
+            core.containers.push(ContainerBound {
+                container: container_flag,
+                area: sub_core.area,
+                start,
+                end,
+            });
+        }
+        core.containers.extend(sub_core.containers.iter().map(|b| ContainerBound {
+            container: b.container.clone(),
+            area: b.area,
+            start: start + b.start,
+            end: start + b.end,
+        }));
+
+        self
+    }
+
+    /// The container-flag, if this Focus was built with [Focus::new_container].
+    pub fn container_flag(&self) -> Option<ContainerFlag> {
+        self.core.read().unwrap().container.clone()
+    }
+
+    /// The area of the container, if any.
+    pub fn container_area(&self) -> Rect {
+        self.core.read().unwrap().area
+    }
+
+    /// Confines subsequent Tab/BackTab/arrow navigation to the widgets
+    /// belonging to `container`. Tab/BackTab that would leave the
+    /// container wraps inside it instead; arrow navigation does not wrap
+    /// and stays put when no widget of the container lies in that
+    /// direction. Mouse clicks outside it are ignored for focus purposes.
+    ///
+    /// This is the core primitive for modal dialogs and popups: fence
+    /// the dialog's container while it's open, and [Focus::unlock] when
+    /// it closes. `container` must have been added via
+    /// [Focus::add_container] (or be this Focus's own container);
+    /// returns false and does nothing otherwise.
+    pub fn lock(&self, container: &ContainerFlag) -> bool {
+        let mut core = self.core.write().unwrap();
+        let is_own = core.container.as_ref() == Some(container);
+        let is_sub = core.containers.iter().any(|b| b.container == *container);
+        if is_own || is_sub {
+            core.lock = Some(container.clone());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Releases a lock set with [Focus::lock], restoring global navigation.
+    pub fn unlock(&self) {
+        self.core.write().unwrap().lock = None;
+    }
+
+    /// Is navigation currently locked to a container?
+    pub fn is_locked(&self) -> bool {
+        self.core.read().unwrap().lock.is_some()
+    }
+
+    /// Gives the focus to the first navigable widget, if none has it yet.
+    pub fn first(&self) {
+        let mut core = self.core.write().unwrap();
+        if core.list.iter().any(|e| e.focus.get()) {
+            return;
+        }
+        clear_changed(&mut core);
+        let first = core
+            .list
+            .iter()
+            .position(|e| navigable_as_destination(e.navigable));
+        let events = transfer(&mut core, None, first, FocusReason::Programmatic);
+        drop(core);
+        events.fire();
+    }
+
+    /// Explicitly sets the focus to the given widget, clearing all others.
+    pub fn focus(&self, widget: &dyn HasFocusFlag) {
+        let flag = widget.focus();
+        self.focus_flag(&flag);
+    }
+
+    /// Explicitly sets the focus to the widget owning the given flag.
+    pub fn focus_flag(&self, flag: &FocusFlag) {
+        let mut core = self.core.write().unwrap();
+        clear_changed(&mut core);
+        let Some(new_idx) = core.list.iter().position(|e| e.focus == *flag) else {
+            return;
+        };
+        let cur = core.list.iter().position(|e| e.focus.get());
+        let events = transfer(&mut core, cur, Some(new_idx), FocusReason::Programmatic);
+        drop(core);
+        events.fire();
+    }
+
+    fn next_idx(core: &FocusCore, from: Option<usize>) -> Option<usize> {
+        let (lo, hi) = locked_bound(core).unwrap_or((0, core.list.len()));
+        if hi <= lo {
+            return None;
+        }
+        let len = hi - lo;
+        let start = match from {
+            Some(v) if v >= lo && v < hi => (v - lo + 1) % len,
+            _ => 0,
+        };
+        for offset in 0..len {
+            let idx = lo + (start + offset) % len;
+            if navigable_as_destination(core.list[idx].navigable) {
+                return Some(idx);
+            }
+        }
+        None
+    }
+
+    fn prev_idx(core: &FocusCore, from: Option<usize>) -> Option<usize> {
+        let (lo, hi) = locked_bound(core).unwrap_or((0, core.list.len()));
+        if hi <= lo {
+            return None;
+        }
+        let len = hi - lo;
+        let start = match from {
+            Some(v) if v >= lo && v < hi => (v - lo + len - 1) % len,
+            _ => len - 1,
+        };
+        for offset in 0..len {
+            let idx = lo + (start + len - offset) % len;
+            if navigable_as_destination(core.list[idx].navigable) {
+                return Some(idx);
+            }
+        }
+        None
+    }
+
+    /// Moves the focus to the next widget in tab order.
+    pub fn next(&self) -> bool {
+        let mut core = self.core.write().unwrap();
+        clear_changed(&mut core);
+        let cur = core.list.iter().position(|e| e.focus.get());
+        if let Some(idx) = cur {
+            if !can_leave_forward(core.list[idx].navigable) {
+                return false;
+            }
+        }
+        let next = Self::next_idx(&core, cur);
+        let events = transfer(&mut core, cur, next, FocusReason::Keyboard);
+        drop(core);
+        events.fire()
+    }
+
+    /// Moves the focus to the previous widget in tab order.
+    pub fn prev(&self) -> bool {
+        let mut core = self.core.write().unwrap();
+        clear_changed(&mut core);
+        let cur = core.list.iter().position(|e| e.focus.get());
+        if let Some(idx) = cur {
+            if !can_leave_backward(core.list[idx].navigable) {
+                return false;
+            }
+        }
+        let prev = Self::prev_idx(&core, cur);
+        let events = transfer(&mut core, cur, prev, FocusReason::KeyboardBackward);
+        drop(core);
+        events.fire()
+    }
+
+    fn focus_dir(&self, dir: Direction) -> bool {
+        let mut core = self.core.write().unwrap();
+        clear_changed(&mut core);
+
+        let Some(cur_idx) = core.list.iter().position(|e| e.focus.get()) else {
+            return false;
+        };
+        let cur_nav = core.list[cur_idx].navigable;
+        let can_leave = if direction_leaves_forward(dir) {
+            can_leave_forward(cur_nav)
+        } else {
+            can_leave_backward(cur_nav)
+        };
+        if !can_leave {
+            return false;
+        }
+        let cur_center = center(core.list[cur_idx].area);
+        let bound = locked_bound(&core);
+
+        let mut best: Option<(usize, i64)> = None;
+        for (idx, entry) in core.list.iter().enumerate() {
+            if idx == cur_idx {
+                continue;
+            }
+            if let Some((lo, hi)) = bound {
+                if idx < lo || idx >= hi {
+                    continue;
+                }
+            }
+            if !navigable_as_destination(entry.navigable) {
+                continue;
+            }
+
+            // Score against the nearest sub-rect, if the widget has disjoint areas.
+            let whole_area;
+            let candidates: &[ZRect] = if entry.z_areas.is_empty() {
+                whole_area = [ZRect::from(entry.area)];
+                &whole_area
+            } else {
+                &entry.z_areas
+            };
+
+            for cand in candidates {
+                if !in_direction(dir, cur_center, cand.area) {
+                    continue;
+                }
+                let score = direction_score(dir, cur_center, center(cand.area));
+                let better = match best {
+                    Some((_, best_score)) => score < best_score,
+                    None => true,
+                };
+                if better {
+                    best = Some((idx, score));
+                }
+            }
+        }
+
+        let events = match best {
+            Some((idx, _)) => transfer(&mut core, Some(cur_idx), Some(idx), FocusReason::Keyboard),
+            None => return false,
+        };
+        drop(core);
+        events.fire()
+    }
+
+    /// Moves the focus to the nearest navigable widget to the left of the
+    /// currently focused one, based on [HasFocusFlag::area]/[HasFocusFlag::z_areas].
+    pub fn focus_left(&self) -> bool {
+        self.focus_dir(Direction::Left)
+    }
+
+    /// Moves the focus to the nearest navigable widget to the right.
+    pub fn focus_right(&self) -> bool {
+        self.focus_dir(Direction::Right)
+    }
+
+    /// Moves the focus to the nearest navigable widget above.
+    pub fn focus_up(&self) -> bool {
+        self.focus_dir(Direction::Up)
+    }
+
+    /// Moves the focus to the nearest navigable widget below.
+    pub fn focus_down(&self) -> bool {
+        self.focus_dir(Direction::Down)
+    }
+
+    /// Handles a mouse click, giving focus to the widget under the cursor.
+    fn mouse_at(&self, x: u16, y: u16) -> bool {
+        let mut core = self.core.write().unwrap();
+        clear_changed(&mut core);
+
+        let bound = locked_bound(&core);
+
+        let mut hit: Option<(usize, u16)> = None;
+        for (idx, entry) in core.list.iter().enumerate() {
+            if entry.navigable == Navigation::None {
+                continue;
+            }
+            if let Some((lo, hi)) = bound {
+                if idx < lo || idx >= hi {
+                    continue;
+                }
+            }
+            if entry.z_areas.is_empty() {
+                if ZRect::from(entry.area).contains(x, y) && hit.map(|(_, hz)| hz == 0).unwrap_or(true) {
+                    hit = Some((idx, 0));
+                }
+            } else {
+                for z in &entry.z_areas {
+                    if z.contains(x, y) && hit.map(|(_, hz)| z.z >= hz).unwrap_or(true) {
+                        hit = Some((idx, z.z));
+                    }
+                }
+            }
+        }
+
+        let events = match hit {
+            Some((idx, _)) => {
+                let cur = core.list.iter().position(|e| e.focus.get());
+                if cur == Some(idx) {
+                    return false;
+                }
+                transfer(&mut core, cur, Some(idx), FocusReason::Mouse)
+            }
+            None => return false,
+        };
+        drop(core);
+        events.fire()
+    }
+}
+
+fn in_direction(dir: Direction, cur_center: (i32, i32), cand: Rect) -> bool {
+    let (ccx, ccy) = cur_center;
+    let (ox, oy) = center(cand);
+    match dir {
+        Direction::Right => {
+            ox >= ccx && (ox - ccx) >= (oy - ccy).abs()
+        }
+        Direction::Left => {
+            ox <= ccx && (ccx - ox) >= (ccy - oy).abs()
+        }
+        Direction::Down => {
+            oy >= ccy && (oy - ccy) >= (ox - ccx).abs()
+        }
+        Direction::Up => {
+            oy <= ccy && (ccy - oy) >= (ccx - ox).abs()
+        }
+    }
+}
+
+/// Weighted Manhattan distance: heavily penalizes offset perpendicular to
+/// the requested direction, so a widget directly ahead beats one far off-axis.
+fn direction_score(dir: Direction, cur_center: (i32, i32), cand_center: (i32, i32)) -> i64 {
+    let dx = (cand_center.0 - cur_center.0) as i64;
+    let dy = (cand_center.1 - cur_center.1) as i64;
+    match dir {
+        Direction::Left | Direction::Right => dx.abs() + dy.abs() * 3,
+        Direction::Up | Direction::Down => dy.abs() + dx.abs() * 3,
+    }
+}
+
+/// Clears all transient gained/lost flags, for the start of a new transition.
+fn clear_changed(core: &mut FocusCore) {
+    for entry in core.list.iter_mut() {
+        entry.focus.set_gained(false);
+        entry.focus.set_lost(false);
+    }
+    for bound in core.containers.iter_mut() {
+        bound.container.set_gained(false);
+        bound.container.set_lost(false);
+        bound.container.set_child_focus_changed(false);
+    }
+    if let Some(container) = &core.container {
+        container.set_gained(false);
+        container.set_lost(false);
+        container.set_child_focus_changed(false);
+    }
+}
+
+/// The callbacks a [transfer] wants fired, collected while `core` is
+/// locked and run by the caller only after the lock has been released
+/// (see [TransferEvents::fire]). This lets an `on_gained`/`on_lost`
+/// callback freely call back into the same `Focus` — e.g. redirect focus
+/// or set up a lock — without deadlocking on its own write lock.
+#[derive(Default)]
+struct TransferEvents {
+    changed: bool,
+    lost: Option<FocusFlag>,
+    lost_containers: Vec<ContainerFlag>,
+    gained: Option<FocusFlag>,
+    gained_containers: Vec<ContainerFlag>,
+}
+
+impl TransferEvents {
+    /// Runs the collected callbacks, lost-then-gained, and returns whether
+    /// the focus actually changed. Call this only after dropping the
+    /// `FocusCore` write guard that [transfer] was given.
+    fn fire(self) -> bool {
+        if let Some(flag) = &self.lost {
+            flag.fire_lost();
+        }
+        for container in &self.lost_containers {
+            container.fire_lost();
+        }
+        if let Some(flag) = &self.gained {
+            flag.fire_gained();
+        }
+        for container in &self.gained_containers {
+            container.fire_gained();
+        }
+        self.changed
+    }
+}
+
+/// Moves the focus from `from` to `to`, updating gained/lost and the
+/// summarizing container flags along the way. Returns the callbacks this
+/// transfer should fire; the caller must drop its `core` guard and then
+/// call [TransferEvents::fire].
+fn transfer(
+    core: &mut FocusCore,
+    from: Option<usize>,
+    to: Option<usize>,
+    reason: FocusReason,
+) -> TransferEvents {
+    if from == to {
+        return TransferEvents::default();
+    }
+
+    let mut events = TransferEvents {
+        changed: true,
+        ..Default::default()
+    };
+
+    if let Some(from) = from {
+        core.list[from].focus.set(false);
+        core.list[from].focus.set_lost(true);
+        core.list[from].focus.set_reason(reason);
+        events.lost = Some(core.list[from].focus.clone());
+    }
+    if let Some(to) = to {
+        core.list[to].focus.set(true);
+        core.list[to].focus.set_gained(true);
+        core.list[to].focus.set_reason(reason);
+        events.gained = Some(core.list[to].focus.clone());
+    }
+
+    // This Focus's own container, if any, spans the whole list.
+    let own = core.container.clone().map(|c| (c, 0, core.list.len()));
+    let bounds = core
+        .containers
+        .iter()
+        .map(|b| (b.container.clone(), b.start, b.end));
+    for (container, start, end) in bounds.chain(own) {
+        let had_focus = from.is_some_and(|idx| start <= idx && idx < end);
+        let has_focus = to.is_some_and(|idx| start <= idx && idx < end);
+        if !had_focus && has_focus {
+            container.set(true);
+            container.set_gained(true);
+            container.set_reason(FocusReason::ContainerEnter);
+            events.gained_containers.push(container);
+        } else if had_focus && !has_focus {
+            container.set(false);
+            container.set_lost(true);
+            container.set_reason(FocusReason::ContainerLeave);
+            events.lost_containers.push(container);
+        } else if had_focus && has_focus {
+            // Focus moved between two children of the same already-focused
+            // container: neither gained nor lost, but the child did change.
+            container.set_child_focus_changed(true);
+        }
+    }
+
+    events
+}
+
+impl HandleEvent<crossterm::event::Event, Regular, Outcome> for Focus {
+    fn handle(&mut self, event: &crossterm::event::Event, _qualifier: Regular) -> Outcome {
+        let r = match event {
+            ct_event!(keycode press Tab) => self.next(),
+            ct_event!(keycode press SHIFT-BackTab) => self.prev(),
+            ct_event!(keycode press Left) => self.focus_left(),
+            ct_event!(keycode press Right) => self.focus_right(),
+            ct_event!(keycode press Up) => self.focus_up(),
+            ct_event!(keycode press Down) => self.focus_down(),
+            _ => false,
+        };
+        if r {
+            Outcome::Changed
+        } else {
+            self.handle(event, MouseOnly)
+        }
+    }
+}
+
+impl HandleEvent<crossterm::event::Event, MouseOnly, Outcome> for Focus {
+    fn handle(&mut self, event: &crossterm::event::Event, _qualifier: MouseOnly) -> Outcome {
+        match event {
+            ct_event!(mouse down Left for x, y) => {
+                if self.mouse_at(*x, *y) {
+                    Outcome::Changed
+                } else {
+                    Outcome::Continue
+                }
+            }
+            _ => Outcome::Continue,
+        }
+    }
+}
+
+/// Handles all focus related keyboard events: `Tab`/`BackTab` for linear
+/// navigation and the arrow keys for spatial navigation (see
+/// [Focus::focus_left]/[Focus::focus_right]/[Focus::focus_up]/[Focus::focus_down]).
+///
+/// Falls through to [handle_mouse_focus] for anything it doesn't consume.
+pub fn handle_focus(focus: &mut Focus, event: &crossterm::event::Event) -> Outcome {
+    focus.handle(event, Regular)
+}
+
+/// Handles mouse clicks that should move the focus.
+pub fn handle_mouse_focus(focus: &mut Focus, event: &crossterm::event::Event) -> Outcome {
+    focus.handle(event, MouseOnly)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct TestWidget {
+        focus: FocusFlag,
+        area: Rect,
+        navigable: Navigation,
+    }
+
+    impl TestWidget {
+        fn new(name: &str, area: Rect) -> Self {
+            Self {
+                focus: FocusFlag::named(name),
+                area,
+                navigable: Navigation::Regular,
+            }
+        }
+
+        fn with_navigable(name: &str, area: Rect, navigable: Navigation) -> Self {
+            Self {
+                navigable,
+                ..Self::new(name, area)
+            }
+        }
+    }
+
+    impl HasFocusFlag for TestWidget {
+        fn focus(&self) -> FocusFlag {
+            self.focus.clone()
+        }
+
+        fn area(&self) -> Rect {
+            self.area
+        }
+
+        fn navigable(&self) -> Navigation {
+            self.navigable
+        }
+    }
+
+    struct TestContainer {
+        container: ContainerFlag,
+        area: Rect,
+        a: TestWidget,
+        b: TestWidget,
+    }
+
+    impl HasFocus for TestContainer {
+        fn focus(&self) -> Focus {
+            let mut focus = Focus::default();
+            focus.add(&self.a);
+            focus.add(&self.b);
+            focus
+        }
+
+        fn container(&self) -> Option<ContainerFlag> {
+            Some(self.container.clone())
+        }
+
+        fn area(&self) -> Rect {
+            self.area
+        }
+    }
+
+    fn rect(x: u16, y: u16, width: u16, height: u16) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn callbacks_fire_lost_before_gained() {
+        let a = TestWidget::new("a", rect(0, 0, 10, 1));
+        let b = TestWidget::new("b", rect(0, 1, 10, 1));
+        let focus = Focus::new(&[&a, &b]);
+        focus.first();
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let order_lost = order.clone();
+        a.focus.on_lost(move || order_lost.lock().unwrap().push("a-lost"));
+        let order_gained = order.clone();
+        b.focus
+            .on_gained(move || order_gained.lock().unwrap().push("b-gained"));
+
+        assert!(focus.next());
+        assert_eq!(*order.lock().unwrap(), vec!["a-lost", "b-gained"]);
+    }
+
+    #[test]
+    fn callback_can_reenter_focus_without_deadlock() {
+        let a = TestWidget::new("a", rect(0, 0, 10, 1));
+        let b = TestWidget::new("b", rect(0, 1, 10, 1));
+        let focus = Focus::new(&[&a, &b]);
+        focus.first();
+
+        // Firing callbacks while still holding `core`'s write lock would
+        // deadlock here, since `focus_flag` below needs that same lock.
+        let focus_clone = focus.clone();
+        let b_flag = b.focus.clone();
+        a.focus
+            .on_lost(move || focus_clone.focus_flag(&b_flag));
+
+        assert!(focus.next());
+        assert!(b.focus.get());
+    }
+
+    #[test]
+    fn in_direction_prefers_on_axis_over_off_axis() {
+        let center = (50, 50);
+        // Straight to the right: on-axis.
+        assert!(in_direction(Direction::Right, center, rect(60, 50, 10, 1)));
+        // Down and to the right, but mostly downward: not "right" of center.
+        assert!(!in_direction(Direction::Right, center, rect(55, 90, 10, 1)));
+        // Behind, to the left: never a "right" candidate.
+        assert!(!in_direction(Direction::Right, center, rect(10, 50, 10, 1)));
+    }
+
+    #[test]
+    fn direction_score_penalizes_perpendicular_offset() {
+        let center = (50, 50);
+        // Directly to the right vs. same horizontal offset but far below:
+        // the off-axis one must score worse (higher).
+        let on_axis = direction_score(Direction::Right, center, (70, 50));
+        let off_axis = direction_score(Direction::Right, center, (70, 70));
+        assert!(on_axis < off_axis);
+    }
+
+    #[test]
+    fn focus_dir_picks_nearest_widget_in_direction() {
+        let left = TestWidget::new("left", rect(0, 0, 10, 3));
+        let right_near = TestWidget::new("right_near", rect(20, 0, 10, 3));
+        let right_far = TestWidget::new("right_far", rect(60, 0, 10, 3));
+        let focus = Focus::new(&[&left, &right_near, &right_far]);
+        focus.focus(&left);
+
+        assert!(focus.focus_right());
+        assert!(right_near.focus.get());
+    }
+
+    #[test]
+    fn focus_dir_respects_reach_navigation() {
+        // A Reach widget (e.g. a multi-line TextArea) captures arrow keys
+        // itself; Focus must not steal it away from under it.
+        let area = TestWidget::with_navigable("area", rect(0, 0, 10, 3), Navigation::Reach);
+        let right = TestWidget::new("right", rect(20, 0, 10, 3));
+        let focus = Focus::new(&[&area, &right]);
+        focus.focus(&area);
+
+        assert!(!focus.focus_right());
+        assert!(area.focus.get());
+    }
+
+    #[test]
+    fn focus_dir_reaches_reach_leave_front_and_back() {
+        // ReachLeaveFront/ReachLeaveBack are destinations for Tab/BackTab
+        // (see navigable_as_destination) and must be arrow-key destinations
+        // too, the same as a plain Regular widget.
+        let left = TestWidget::new("left", rect(0, 0, 10, 3));
+        let front = TestWidget::with_navigable(
+            "front",
+            rect(20, 0, 10, 3),
+            Navigation::ReachLeaveFront,
+        );
+        let focus = Focus::new(&[&left, &front]);
+        focus.focus(&left);
+
+        assert!(focus.focus_right());
+        assert!(front.focus.get());
+
+        let left2 = TestWidget::new("left2", rect(0, 0, 10, 3));
+        let back =
+            TestWidget::with_navigable("back", rect(20, 0, 10, 3), Navigation::ReachLeaveBack);
+        let focus = Focus::new(&[&left2, &back]);
+        focus.focus(&left2);
+
+        assert!(focus.focus_right());
+        assert!(back.focus.get());
+    }
+
+    #[test]
+    fn lock_confines_navigation_to_sub_container() {
+        let outside = TestWidget::new("outside", rect(0, 0, 10, 1));
+        let dialog = TestContainer {
+            container: ContainerFlag::named("dialog"),
+            area: rect(0, 10, 10, 10),
+            a: TestWidget::new("dialog-a", rect(0, 10, 10, 1)),
+            b: TestWidget::new("dialog-b", rect(0, 11, 10, 1)),
+        };
+
+        let mut focus = Focus::new(&[&outside]);
+        focus.add_container(&dialog);
+        focus.focus(&dialog.a);
+
+        assert!(focus.lock(&dialog.container));
+        assert!(focus.next());
+        assert!(dialog.b.focus.get());
+        // Wraps inside the locked container instead of reaching `outside`.
+        assert!(focus.next());
+        assert!(dialog.a.focus.get());
+
+        focus.unlock();
+        assert!(focus.next());
+        assert!(dialog.b.focus.get());
+        assert!(focus.next());
+        assert!(outside.focus.get());
+    }
+
+    #[test]
+    fn lock_accepts_this_focus_own_container() {
+        let dialog = TestContainer {
+            container: ContainerFlag::named("dialog"),
+            area: rect(0, 0, 10, 10),
+            a: TestWidget::new("dialog-a", rect(0, 0, 10, 1)),
+            b: TestWidget::new("dialog-b", rect(0, 1, 10, 1)),
+        };
+
+        let focus = Focus::new_container(&dialog);
+        focus.focus(&dialog.a);
+
+        assert!(focus.lock(&dialog.container));
+        assert!(focus.next());
+        assert!(dialog.b.focus.get());
+        assert!(focus.next());
+        assert!(dialog.a.focus.get());
+    }
+
+    #[test]
+    fn lock_rejects_unknown_container() {
+        let a = TestWidget::new("a", rect(0, 0, 10, 1));
+        let focus = Focus::new(&[&a]);
+        assert!(!focus.lock(&ContainerFlag::named("unrelated")));
+        assert!(!focus.is_locked());
+    }
+
+    #[test]
+    fn next_and_prev_set_keyboard_reasons() {
+        let a = TestWidget::new("a", rect(0, 0, 10, 1));
+        let b = TestWidget::new("b", rect(0, 1, 10, 1));
+        let focus = Focus::new(&[&a, &b]);
+        focus.first();
+
+        assert!(focus.next());
+        assert_eq!(b.focus.reason(), FocusReason::Keyboard);
+
+        assert!(focus.prev());
+        assert_eq!(a.focus.reason(), FocusReason::KeyboardBackward);
+    }
+
+    #[test]
+    fn mouse_at_sets_mouse_reason() {
+        let a = TestWidget::new("a", rect(0, 0, 10, 1));
+        let b = TestWidget::new("b", rect(0, 1, 10, 1));
+        let focus = Focus::new(&[&a, &b]);
+        focus.first();
+
+        assert!(focus.mouse_at(0, 1));
+        assert_eq!(b.focus.reason(), FocusReason::Mouse);
+    }
+
+    #[test]
+    fn focus_and_focus_flag_set_programmatic_reason() {
+        let a = TestWidget::new("a", rect(0, 0, 10, 1));
+        let b = TestWidget::new("b", rect(0, 1, 10, 1));
+        let focus = Focus::new(&[&a, &b]);
+        focus.focus(&a);
+        assert_eq!(a.focus.reason(), FocusReason::Programmatic);
+
+        focus.focus_flag(&b.focus.clone());
+        assert_eq!(b.focus.reason(), FocusReason::Programmatic);
+    }
+
+    #[test]
+    fn entering_and_leaving_a_container_sets_container_reasons() {
+        let outside = TestWidget::new("outside", rect(0, 0, 10, 1));
+        let dialog = TestContainer {
+            container: ContainerFlag::named("dialog"),
+            area: rect(0, 10, 10, 10),
+            a: TestWidget::new("dialog-a", rect(0, 10, 10, 1)),
+            b: TestWidget::new("dialog-b", rect(0, 11, 10, 1)),
+        };
+
+        let mut focus = Focus::new(&[&outside]);
+        focus.add_container(&dialog);
+        focus.focus(&outside);
+
+        assert!(focus.next());
+        assert!(dialog.a.focus.get());
+        assert_eq!(dialog.container.reason(), FocusReason::ContainerEnter);
+
+        assert!(focus.next());
+        assert!(dialog.b.focus.get());
+        assert!(dialog.container.child_focus_changed());
+
+        assert!(focus.next());
+        assert!(outside.focus.get());
+        assert_eq!(dialog.container.reason(), FocusReason::ContainerLeave);
+    }
+}
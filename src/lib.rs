@@ -13,6 +13,11 @@ use std::sync::{Arc, RwLock};
 pub use crate::focus::{handle_focus, handle_mouse_focus, Focus};
 pub use crate::zrect::ZRect;
 
+/// Derives [HasFocus] for a widget-state struct from its `#[focus]`-annotated
+/// fields. See `rat_focus_derive` for the attributes it understands.
+#[cfg(feature = "derive")]
+pub use rat_focus_derive::HasFocus;
+
 pub mod event {
     //! Rexported eventhandling traits.
     pub use rat_event::{
@@ -101,6 +106,45 @@ struct FocusFlagCore {
     ///
     /// See [on_lost!](crate::on_lost!)
     lost: RwLock<bool>,
+    /// Why the focus last changed. Valid during the gained/lost window,
+    /// same as `gained`/`lost` themselves.
+    reason: RwLock<FocusReason>,
+    /// Called by [Focus::handle] right after this flag gains the focus.
+    on_gained: RwLock<Option<Box<dyn FnMut() + Send + Sync>>>,
+    /// Called by [Focus::handle] right after this flag loses the focus.
+    on_lost: RwLock<Option<Box<dyn FnMut() + Send + Sync>>>,
+    /// Container only: the focused descendant changed, but the container
+    /// itself was already focused and stays focused. See
+    /// [ContainerFlag::child_focus_changed].
+    child_changed: RwLock<bool>,
+}
+
+/// Why a widget gained or lost the focus.
+///
+/// Set by [Focus::handle] alongside the `gained`/`lost` flags, and
+/// readable through [FocusFlag::reason] for the duration that
+/// `gained`/`lost` are set. This lets `on_gained!`/`on_lost!` bodies
+/// distinguish e.g. "user tabbed in" from "clicked in" from "focus
+/// restored programmatically".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FocusReason {
+    /// Moved here by Tab, or by an arrow key in any direction.
+    Keyboard,
+    /// Moved here by backward keyboard navigation (BackTab/Shift-Tab).
+    KeyboardBackward,
+    /// Moved here by a mouse click.
+    Mouse,
+    /// Set explicitly via [Focus::focus]/[Focus::focus_flag], not through
+    /// a navigation event. Also the default, matching a flag that hasn't
+    /// been touched by any navigation yet.
+    #[default]
+    Programmatic,
+    /// The containing [ContainerFlag] gained the focus because one of
+    /// its children did.
+    ContainerEnter,
+    /// The containing [ContainerFlag] lost the focus because none of
+    /// its children has it anymore.
+    ContainerLeave,
 }
 
 /// Focus navigation for widgets.
@@ -285,6 +329,43 @@ impl FocusFlag {
         *(self.0.gained.write().unwrap()) = gained;
     }
 
+    /// Why the focus last changed. Valid during the gained/lost window.
+    #[inline]
+    pub fn reason(&self) -> FocusReason {
+        *(self.0.reason.read().unwrap())
+    }
+
+    #[inline]
+    pub(crate) fn set_reason(&self, reason: FocusReason) {
+        *(self.0.reason.write().unwrap()) = reason;
+    }
+
+    /// Registers a callback invoked right after this flag gains the focus.
+    /// Replaces any previously registered callback.
+    pub fn on_gained(&self, f: impl FnMut() + Send + Sync + 'static) {
+        *(self.0.on_gained.write().unwrap()) = Some(Box::new(f));
+    }
+
+    /// Registers a callback invoked right after this flag loses the focus.
+    /// Replaces any previously registered callback.
+    pub fn on_lost(&self, f: impl FnMut() + Send + Sync + 'static) {
+        *(self.0.on_lost.write().unwrap()) = Some(Box::new(f));
+    }
+
+    /// Runs the `on_gained` callback, if any.
+    pub(crate) fn fire_gained(&self) {
+        if let Some(f) = self.0.on_gained.write().unwrap().as_mut() {
+            f();
+        }
+    }
+
+    /// Runs the `on_lost` callback, if any.
+    pub(crate) fn fire_lost(&self) {
+        if let Some(f) = self.0.on_lost.write().unwrap().as_mut() {
+            f();
+        }
+    }
+
     /// Reset all flags to false.
     #[inline]
     pub fn clear(&self) {
@@ -345,12 +426,65 @@ impl ContainerFlag {
         *(self.0.gained.write().unwrap()) = gained
     }
 
+    /// Why the focus last changed. Valid during the gained/lost window.
+    #[inline]
+    pub fn reason(&self) -> FocusReason {
+        *(self.0.reason.read().unwrap())
+    }
+
+    #[inline]
+    pub(crate) fn set_reason(&self, reason: FocusReason) {
+        *(self.0.reason.write().unwrap()) = reason;
+    }
+
+    /// Registers a callback invoked right after this flag gains the focus.
+    /// Replaces any previously registered callback.
+    pub fn on_gained(&self, f: impl FnMut() + Send + Sync + 'static) {
+        *(self.0.on_gained.write().unwrap()) = Some(Box::new(f));
+    }
+
+    /// Registers a callback invoked right after this flag loses the focus.
+    /// Replaces any previously registered callback.
+    pub fn on_lost(&self, f: impl FnMut() + Send + Sync + 'static) {
+        *(self.0.on_lost.write().unwrap()) = Some(Box::new(f));
+    }
+
+    /// Runs the `on_gained` callback, if any.
+    pub(crate) fn fire_gained(&self) {
+        if let Some(f) = self.0.on_gained.write().unwrap().as_mut() {
+            f();
+        }
+    }
+
+    /// Runs the `on_lost` callback, if any.
+    pub(crate) fn fire_lost(&self) {
+        if let Some(f) = self.0.on_lost.write().unwrap().as_mut() {
+            f();
+        }
+    }
+
+    /// The focused descendant changed, while the container itself was and
+    /// still is focused. This is distinct from [ContainerFlag::gained]/
+    /// [ContainerFlag::lost], which only fire when focus enters the
+    /// container from outside, or leaves it entirely.
+    #[inline]
+    pub fn child_focus_changed(&self) -> bool {
+        *(self.0.child_changed.read().unwrap())
+    }
+
+    #[inline]
+    pub(crate) fn set_child_focus_changed(&self, changed: bool) {
+        *(self.0.child_changed.write().unwrap()) = changed;
+    }
+
     /// Reset all flags to false.
     #[inline]
     pub fn clear(&self) {
         *(self.0.focus.write().unwrap()) = false;
         *(self.0.lost.write().unwrap()) = false;
-        *(self.0.gained.write().unwrap()) = false;    }
+        *(self.0.gained.write().unwrap()) = false;
+        *(self.0.child_changed.write().unwrap()) = false;
+    }
 }
 
 impl FocusFlagCore {
@@ -360,6 +494,10 @@ impl FocusFlagCore {
             focus: RwLock::new(false),
             gained: RwLock::new(false),
             lost: RwLock::new(false),
+            reason: RwLock::new(FocusReason::default()),
+            on_gained: RwLock::new(None),
+            on_lost: RwLock::new(None),
+            child_changed: RwLock::new(false),
         }
     }
 }
@@ -453,3 +591,42 @@ macro_rules! match_focus {
         $(else { $final })?
     }};
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn focus_flag_default_reason_is_programmatic() {
+        let flag = FocusFlag::named("a");
+        assert_eq!(flag.reason(), FocusReason::Programmatic);
+        flag.set_reason(FocusReason::Keyboard);
+        assert_eq!(flag.reason(), FocusReason::Keyboard);
+    }
+
+    #[test]
+    fn focus_flag_clear_resets_gained_and_lost() {
+        let flag = FocusFlag::named("a");
+        flag.set_gained(true);
+        flag.set_lost(true);
+        assert!(flag.gained());
+        assert!(flag.lost());
+        flag.clear();
+        assert!(!flag.gained());
+        assert!(!flag.lost());
+    }
+
+    #[test]
+    fn container_flag_clear_resets_child_focus_changed() {
+        let container = ContainerFlag::named("c");
+        container.set_gained(true);
+        container.set_lost(true);
+        container.set_child_focus_changed(true);
+
+        container.clear();
+
+        assert!(!container.gained());
+        assert!(!container.lost());
+        assert!(!container.child_focus_changed());
+    }
+}
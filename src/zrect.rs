@@ -0,0 +1,41 @@
+//! A rect with an additional z-index.
+use ratatui::layout::Rect;
+
+/// A [Rect] with a z-index.
+///
+/// Widgets that render more than one disjoint area (e.g. a popup that
+/// extends outside the widget's main area) report those sub-areas as
+/// a list of `ZRect` via [HasFocusFlag::z_areas](crate::HasFocusFlag::z_areas).
+/// Higher `z` wins when areas overlap, e.g. for mouse hit-testing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ZRect {
+    pub area: Rect,
+    pub z: u16,
+}
+
+impl From<Rect> for ZRect {
+    fn from(area: Rect) -> Self {
+        Self { area, z: 0 }
+    }
+}
+
+impl From<(Rect, u16)> for ZRect {
+    fn from((area, z): (Rect, u16)) -> Self {
+        Self { area, z }
+    }
+}
+
+impl ZRect {
+    /// New ZRect for the given area and z-index.
+    pub fn new(area: Rect, z: u16) -> Self {
+        Self { area, z }
+    }
+
+    /// Does the area contain the given screen position.
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        x >= self.area.x
+            && x < self.area.x + self.area.width
+            && y >= self.area.y
+            && y < self.area.y + self.area.height
+    }
+}